@@ -2,7 +2,7 @@ use tauri::{AppHandle, State, Emitter};
 use std::sync::Mutex;
 use tokio::sync::mpsc;
 use crate::audio::AudioSystem;
-use crate::transcription::{run_transcription_loop, TranscriptionCommand};
+use crate::transcription::{run_transcription_loop, TranscriptionCommand, TranscriptionConfig};
 use tracing::{info, error};
 
 // cpal::Stream is not Send on Windows because of *mut () raw pointers in WASAPI.
@@ -49,7 +49,7 @@ pub async fn start_recording(state: State<'_, AppState>, app: AppHandle) -> Resu
     let (tx, rx) = mpsc::channel(10);
 
     // Spawn transcription task
-    tokio::spawn(run_transcription_loop(consumer, sample_rate, channels, app.clone(), rx));
+    tokio::spawn(run_transcription_loop(consumer, sample_rate, channels, app.clone(), rx, TranscriptionConfig::from_env()));
     
     recording.stream = Some(SendStream(stream));
     recording.cmd_tx = Some(tx);
@@ -63,7 +63,7 @@ pub async fn start_recording(state: State<'_, AppState>, app: AppHandle) -> Resu
 }
 
 #[tauri::command]
-pub async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
+pub async fn stop_recording(state: State<'_, AppState>, _app: AppHandle) -> Result<(), String> {
     info!("Received stop_recording command");
     
     let cmd_tx = {
@@ -82,9 +82,11 @@ pub async fn stop_recording(state: State<'_, AppState>, app: AppHandle) -> Resul
             error!("Failed to send stop command to transcription task: {}", e);
         }
     }
-    
-    info!("Recording stopped");
-    app.emit("status", "stopped").map_err(|e| e.to_string())?;
+
+    // The transcription task itself emits `status: "stopped"` once it has
+    // actually saved the meeting note — not here, so the UI doesn't tell
+    // the user it's safe to close the app before that's done.
+    info!("Stop requested");
 
     Ok(())
 }
@@ -2,6 +2,8 @@ pub mod audio;
 pub mod transcription;
 pub mod notes;
 pub mod ipc;
+pub mod stt;
+pub mod vad;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -1,8 +1,56 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
-use anyhow::{Result, Context};
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result, Context};
+use std::env;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use rand::RngCore;
+
+/// Marks a note file written by the `Encrypted` sink, so `load_from_file`
+/// knows to derive a key and decrypt before handing back markdown.
+const ENCRYPTED_MAGIC: &[u8; 4] = b"TKF1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Where a rendered meeting note is written. Additional sinks (HTTP POST
+/// to a notes service, JSON export, ...) can be added as new variants
+/// without touching `MeetingNote::save_to_file`'s call site.
+pub enum NoteSink {
+    Plain(PathBuf),
+    Encrypted { path: PathBuf, passphrase: String },
+}
+
+impl NoteSink {
+    pub fn path(&self) -> &Path {
+        match self {
+            NoteSink::Plain(path) => path,
+            NoteSink::Encrypted { path, .. } => path,
+        }
+    }
+
+    /// Picks `Encrypted` when `MEETING_NOTES_PASSPHRASE` is set, `Plain` otherwise.
+    fn from_env(path: PathBuf) -> Self {
+        match env::var("MEETING_NOTES_PASSPHRASE") {
+            Ok(passphrase) if !passphrase.is_empty() => NoteSink::Encrypted { path, passphrase },
+            _ => NoteSink::Plain(path),
+        }
+    }
+
+    fn write(&self, content: &str) -> Result<()> {
+        match self {
+            NoteSink::Plain(path) => {
+                fs::write(path, content).context("Failed to write meeting note file")
+            }
+            NoteSink::Encrypted { path, passphrase } => {
+                let ciphertext = encrypt(content.as_bytes(), passphrase)?;
+                fs::write(path, ciphertext).context("Failed to write encrypted meeting note file")
+            }
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MeetingNote {
@@ -77,11 +125,75 @@ impl MeetingNote {
         }
 
         let filename = format!("meeting_{}.md", self.timestamp.format("%Y%m%d_%H%M%S"));
-        let path = dir.join(filename);
-        
-        let content = self.format_markdown();
-        fs::write(&path, content).context("Failed to write meeting note file")?;
-        
-        Ok(path)
+        let sink = NoteSink::from_env(dir.join(filename));
+
+        sink.write(&self.format_markdown())?;
+        Ok(sink.path().to_path_buf())
     }
+
+    /// Reads a note previously written by [`MeetingNote::save_to_file`],
+    /// transparently decrypting it if it carries the encrypted-note header.
+    /// Returns the rendered markdown.
+    pub fn load_from_file(path: &Path, passphrase: Option<&str>) -> Result<String> {
+        let bytes = fs::read(path).context("Failed to read meeting note file")?;
+
+        if bytes.len() >= ENCRYPTED_MAGIC.len() && bytes[..ENCRYPTED_MAGIC.len()] == ENCRYPTED_MAGIC[..] {
+            let passphrase = passphrase
+                .ok_or_else(|| anyhow!("Note is encrypted but no passphrase was provided"))?;
+            let plaintext = decrypt(&bytes, passphrase)?;
+            String::from_utf8(plaintext).context("Decrypted note was not valid UTF-8")
+        } else {
+            String::from_utf8(bytes).context("Meeting note file was not valid UTF-8")
+        }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypts `plaintext` with AES-256-GCM under a key derived from
+/// `passphrase`, prefixing the output with magic bytes plus the salt and
+/// nonce that were used so [`decrypt`] can reverse it.
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|e| anyhow!("Encryption failed: {}", e))?;
+
+    let mut out = Vec::with_capacity(ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(ENCRYPTED_MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce_bytes);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let header_len = ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if data.len() < header_len {
+        return Err(anyhow!("Encrypted note is truncated"));
+    }
+
+    let salt = &data[ENCRYPTED_MAGIC.len()..ENCRYPTED_MAGIC.len() + SALT_LEN];
+    let nonce_bytes = &data[ENCRYPTED_MAGIC.len() + SALT_LEN..header_len];
+    let ciphertext = &data[header_len..];
+
+    let key = derive_key(passphrase, salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Nonce::from_slice(nonce_bytes);
+    cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|e| anyhow!("Decryption failed (wrong passphrase?): {}", e))
 }
@@ -0,0 +1,436 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use crc32fast::Hasher as Crc32Hasher;
+use futures::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
+use tracing::error;
+use url::Url;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type HmacSha256 = Hmac<Sha256>;
+
+/// A single piece of recognized speech returned by an STT backend.
+pub struct TranscriptResult {
+    pub text: String,
+    pub is_final: bool,
+}
+
+/// Abstraction over a streaming speech-to-text vendor, so the audio
+/// pipeline in `transcription.rs` doesn't need to know which one is
+/// in use.
+#[async_trait]
+pub trait TranscriptionBackend: Send {
+    async fn connect(&mut self, sample_rate: u32, channels: u16) -> Result<()>;
+    async fn send_audio(&mut self, samples: &[i16]) -> Result<()>;
+    /// Waits for the next transcript event. Returns `None` once the
+    /// underlying connection has closed.
+    async fn next_result(&mut self) -> Option<TranscriptResult>;
+    async fn close(&mut self) -> Result<()>;
+}
+
+// ---------------------------------------------------------------------
+// Deepgram
+// ---------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+struct DeepgramResponse {
+    channel: Option<DeepgramChannel>,
+    is_final: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramChannel {
+    alternatives: Vec<DeepgramAlternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramAlternative {
+    transcript: String,
+}
+
+pub struct DeepgramBackend {
+    api_key: String,
+    ws: Option<WsStream>,
+}
+
+impl DeepgramBackend {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key, ws: None }
+    }
+}
+
+#[async_trait]
+impl TranscriptionBackend for DeepgramBackend {
+    async fn connect(&mut self, sample_rate: u32, channels: u16) -> Result<()> {
+        // encoding=linear16 means raw PCM 16-bit signed little-endian
+        let url_str = format!(
+            "wss://api.deepgram.com/v1/listen?model=nova-2&encoding=linear16&sample_rate={}&channels={}&smart_format=true&interim_results=true",
+            sample_rate, channels
+        );
+        let url = Url::parse(&url_str).map_err(|e| anyhow!("Invalid Deepgram URL: {}", e))?;
+
+        let req = tokio_tungstenite::tungstenite::handshake::client::Request::builder()
+            .uri(url.as_str())
+            .header("Authorization", format!("Token {}", self.api_key))
+            .body(())
+            .map_err(|e| anyhow!("Failed to build Deepgram request: {}", e))?;
+
+        let (ws_stream, _) = connect_async(req)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to Deepgram: {}", e))?;
+
+        self.ws = Some(ws_stream);
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, samples: &[i16]) -> Result<()> {
+        let ws = self.ws.as_mut().ok_or_else(|| anyhow!("Deepgram backend not connected"))?;
+
+        let mut byte_data = Vec::with_capacity(samples.len() * 2);
+        for sample in samples {
+            byte_data.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        ws.send(Message::Binary(byte_data))
+            .await
+            .map_err(|e| anyhow!("WS Send Error: {}", e))
+    }
+
+    async fn next_result(&mut self) -> Option<TranscriptResult> {
+        let ws = self.ws.as_mut()?;
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    if let Ok(response) = serde_json::from_str::<DeepgramResponse>(&text) {
+                        if let Some(channel) = response.channel {
+                            if let Some(alt) = channel.alternatives.first() {
+                                if !alt.transcript.trim().is_empty() {
+                                    return Some(TranscriptResult {
+                                        text: alt.transcript.clone(),
+                                        is_final: response.is_final.unwrap_or(false),
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => return None,
+                Some(Err(e)) => {
+                    error!("WS Receive Error: {}", e);
+                    return None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        if let Some(ws) = self.ws.as_mut() {
+            let _ = ws.send(Message::Close(None)).await;
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------
+// AWS Transcribe (streaming)
+// ---------------------------------------------------------------------
+
+const AWS_EVENT_STREAM_HEADER_STRING_TYPE: u8 = 7;
+
+#[derive(Debug, Deserialize)]
+struct AwsTranscriptEvent {
+    #[serde(rename = "Transcript")]
+    transcript: AwsTranscript,
+}
+
+#[derive(Debug, Deserialize)]
+struct AwsTranscript {
+    #[serde(rename = "Results")]
+    results: Vec<AwsResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AwsResult {
+    #[serde(rename = "IsPartial")]
+    is_partial: bool,
+    #[serde(rename = "Alternatives")]
+    alternatives: Vec<AwsAlternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AwsAlternative {
+    #[serde(rename = "Transcript")]
+    transcript: String,
+}
+
+pub struct AwsTranscribeBackend {
+    region: String,
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    language_code: String,
+    ws: Option<WsStream>,
+}
+
+impl AwsTranscribeBackend {
+    pub fn new(region: String, access_key: String, secret_key: String, session_token: Option<String>) -> Self {
+        Self {
+            region,
+            access_key,
+            secret_key,
+            session_token,
+            language_code: "en-US".to_string(),
+            ws: None,
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionBackend for AwsTranscribeBackend {
+    async fn connect(&mut self, sample_rate: u32, _channels: u16) -> Result<()> {
+        let url_str = presign_transcribe_url(
+            &self.region,
+            &self.access_key,
+            &self.secret_key,
+            self.session_token.as_deref(),
+            &self.language_code,
+            sample_rate,
+        )?;
+        let url = Url::parse(&url_str).map_err(|e| anyhow!("Invalid AWS Transcribe URL: {}", e))?;
+
+        let (ws_stream, _) = connect_async(url.as_str())
+            .await
+            .map_err(|e| anyhow!("Failed to connect to AWS Transcribe: {}", e))?;
+
+        self.ws = Some(ws_stream);
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, samples: &[i16]) -> Result<()> {
+        let ws = self.ws.as_mut().ok_or_else(|| anyhow!("AWS Transcribe backend not connected"))?;
+
+        let mut payload = Vec::with_capacity(samples.len() * 2);
+        for sample in samples {
+            payload.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        let message = encode_audio_event(&payload);
+        ws.send(Message::Binary(message))
+            .await
+            .map_err(|e| anyhow!("WS Send Error: {}", e))
+    }
+
+    async fn next_result(&mut self) -> Option<TranscriptResult> {
+        let ws = self.ws.as_mut()?;
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Binary(bytes))) => {
+                    let payload = match decode_event_stream_message(&bytes) {
+                        Some(p) => p,
+                        None => continue,
+                    };
+                    if let Ok(event) = serde_json::from_slice::<AwsTranscriptEvent>(&payload) {
+                        if let Some(result) = event.transcript.results.first() {
+                            if let Some(alt) = result.alternatives.first() {
+                                if !alt.transcript.trim().is_empty() {
+                                    return Some(TranscriptResult {
+                                        text: alt.transcript.clone(),
+                                        is_final: !result.is_partial,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => return None,
+                Some(Err(e)) => {
+                    error!("WS Receive Error: {}", e);
+                    return None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        if let Some(ws) = self.ws.as_mut() {
+            let _ = ws.send(Message::Close(None)).await;
+        }
+        Ok(())
+    }
+}
+
+/// Builds one Amazon event-stream `AudioEvent` message:
+/// `[total_len: u32 BE][headers_len: u32 BE][prelude_crc32: u32 BE][headers][payload][message_crc32: u32 BE]`
+fn encode_audio_event(payload: &[u8]) -> Vec<u8> {
+    let mut headers = Vec::new();
+    encode_header(&mut headers, ":message-type", "event");
+    encode_header(&mut headers, ":event-type", "AudioEvent");
+    encode_header(&mut headers, ":content-type", "application/octet-stream");
+
+    let headers_len = headers.len() as u32;
+    let total_len = 4 + 4 + 4 + headers_len + payload.len() as u32 + 4;
+
+    let mut prelude = Vec::with_capacity(8);
+    prelude.extend_from_slice(&total_len.to_be_bytes());
+    prelude.extend_from_slice(&headers_len.to_be_bytes());
+    let prelude_crc = crc32(&prelude);
+
+    let mut message = Vec::with_capacity(total_len as usize);
+    message.extend_from_slice(&prelude);
+    message.extend_from_slice(&prelude_crc.to_be_bytes());
+    message.extend_from_slice(&headers);
+    message.extend_from_slice(payload);
+
+    let message_crc = crc32(&message);
+    message.extend_from_slice(&message_crc.to_be_bytes());
+
+    message
+}
+
+/// Extracts the JSON payload from an inbound event-stream `TranscriptEvent`
+/// message, validating both CRC32 checksums.
+fn decode_event_stream_message(bytes: &[u8]) -> Option<Vec<u8>> {
+    if bytes.len() < 16 {
+        return None;
+    }
+
+    let total_len = u32::from_be_bytes(bytes[0..4].try_into().ok()?) as usize;
+    let headers_len = u32::from_be_bytes(bytes[4..8].try_into().ok()?) as usize;
+    let prelude_crc = u32::from_be_bytes(bytes[8..12].try_into().ok()?);
+
+    if total_len != bytes.len() || crc32(&bytes[0..8]) != prelude_crc {
+        return None;
+    }
+
+    let message_crc = u32::from_be_bytes(bytes[total_len - 4..total_len].try_into().ok()?);
+    if crc32(&bytes[0..total_len - 4]) != message_crc {
+        return None;
+    }
+
+    let headers_start = 12;
+    let payload_start = headers_start + headers_len;
+    let payload_end = total_len - 4;
+    if payload_start > payload_end {
+        return None;
+    }
+
+    Some(bytes[payload_start..payload_end].to_vec())
+}
+
+fn encode_header(buf: &mut Vec<u8>, name: &str, value: &str) {
+    buf.push(name.len() as u8);
+    buf.extend_from_slice(name.as_bytes());
+    buf.push(AWS_EVENT_STREAM_HEADER_STRING_TYPE);
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut hasher = Crc32Hasher::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Builds a SigV4-presigned `wss://` URL for the AWS Transcribe streaming
+/// API, following the same presign scheme used for S3 query-string auth.
+fn presign_transcribe_url(
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+    session_token: Option<&str>,
+    language_code: &str,
+    sample_rate: u32,
+) -> Result<String> {
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = now.format("%Y%m%d").to_string();
+
+    let host = format!("transcribestreaming.{}.amazonaws.com:8443", region);
+    let canonical_uri = "/stream-transcription-websocket";
+    let credential_scope = format!("{}/{}/transcribe/aws4_request", date_stamp, region);
+    let credential = format!("{}/{}", access_key, credential_scope);
+
+    let mut params: Vec<(String, String)> = vec![
+        ("X-Amz-Algorithm".into(), "AWS4-HMAC-SHA256".into()),
+        ("X-Amz-Credential".into(), credential),
+        ("X-Amz-Date".into(), amz_date.clone()),
+        ("X-Amz-Expires".into(), "300".into()),
+        ("X-Amz-SignedHeaders".into(), "host".into()),
+        ("language-code".into(), language_code.to_string()),
+        ("media-encoding".into(), "pcm".into()),
+        ("sample-rate".into(), sample_rate.to_string()),
+    ];
+    if let Some(token) = session_token {
+        params.push(("X-Amz-Security-Token".into(), token.to_string()));
+    }
+    params.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let canonical_querystring = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", uri_encode(k), uri_encode(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let canonical_headers = format!("host:{}\n", host);
+    let signed_headers = "host";
+    // Empty body payload hash (sha256 of an empty string).
+    let payload_hash = hex_encode(&Sha256::digest([]));
+
+    let canonical_request = format!(
+        "GET\n{}\n{}\n{}\n{}\n{}",
+        canonical_uri, canonical_querystring, canonical_headers, signed_headers, payload_hash
+    );
+
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        credential_scope,
+        hex_encode(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = derive_signing_key(secret_key, &date_stamp, region, "transcribe");
+    let signature = hex_encode(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    let querystring_with_sig = format!("{}&X-Amz-Signature={}", canonical_querystring, signature);
+
+    Ok(format!("wss://{}{}?{}", host, canonical_uri, querystring_with_sig))
+}
+
+fn derive_signing_key(secret_key: &str, date_stamp: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date_stamp.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// RFC 3986 percent-encoding as required by SigV4 (unreserved characters
+/// are `A-Za-z0-9-_.~`; everything else, including spaces, is `%XX`).
+fn uri_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.as_bytes() {
+        match *byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(*byte as char);
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
@@ -1,222 +1,349 @@
 use ringbuf::{Consumer, SharedRb};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
-use tokio::time::{interval, Duration};
+use tokio::time::{interval, sleep, Duration};
 use tracing::{info, error};
 use tokio::sync::mpsc::Receiver;
 use crate::notes::MeetingNote;
-use url::Url;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use futures::{StreamExt, SinkExt};
-use serde::Deserialize;
+use crate::stt::{AwsTranscribeBackend, DeepgramBackend, TranscriptionBackend};
+use crate::vad::{frame_size_for_rate, VadTransition, VoiceActivityGate};
 use std::env;
+use samplerate::{ConverterType, Samplerate};
+use rand::Rng;
+
+const RECONNECT_BASE_BACKOFF: Duration = Duration::from_millis(250);
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(10);
 
 pub enum TranscriptionCommand {
     Stop,
 }
 
-#[derive(Debug, Deserialize)]
-struct DeepgramResponse {
-    channel: Option<DeepgramChannel>,
-    is_final: Option<bool>,
+/// Which streaming STT vendor to use. Selected via `STT_BACKEND`
+/// (`deepgram` or `aws`), defaulting to Deepgram.
+#[derive(Debug, Clone, Default)]
+pub enum SttBackendKind {
+    #[default]
+    Deepgram,
+    AwsTranscribe,
+}
+
+impl SttBackendKind {
+    fn from_env() -> Self {
+        match env::var("STT_BACKEND").unwrap_or_default().to_lowercase().as_str() {
+            "aws" | "aws_transcribe" | "aws-transcribe" => Self::AwsTranscribe,
+            _ => Self::Deepgram,
+        }
+    }
+}
+
+/// Tunables for the audio pipeline feeding the STT backend.
+#[derive(Debug, Clone)]
+pub struct TranscriptionConfig {
+    /// Sample rate (Hz) audio is resampled to before being streamed out.
+    /// Lower this on constrained networks to cut bandwidth further.
+    pub target_sample_rate: u32,
+    /// Which STT backend to stream audio to.
+    pub backend: SttBackendKind,
+    /// Trailing silent VAD frames still forwarded after speech ends, so
+    /// word endings aren't clipped.
+    pub vad_hangover_frames: usize,
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            target_sample_rate: 16000,
+            backend: SttBackendKind::default(),
+            vad_hangover_frames: 5,
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct DeepgramChannel {
-    alternatives: Vec<DeepgramAlternative>,
+impl TranscriptionConfig {
+    pub fn from_env() -> Self {
+        Self {
+            backend: SttBackendKind::from_env(),
+            ..Self::default()
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct DeepgramAlternative {
-    transcript: String,
+fn build_backend(kind: &SttBackendKind) -> Result<Box<dyn TranscriptionBackend>, String> {
+    match kind {
+        SttBackendKind::Deepgram => {
+            let api_key = env::var("DEEPGRAM_API_KEY").unwrap_or_default();
+            if api_key.is_empty() {
+                return Err("DEEPGRAM_API_KEY not found in environment variables".into());
+            }
+            Ok(Box::new(DeepgramBackend::new(api_key)))
+        }
+        SttBackendKind::AwsTranscribe => {
+            let access_key = env::var("AWS_ACCESS_KEY_ID").unwrap_or_default();
+            let secret_key = env::var("AWS_SECRET_ACCESS_KEY").unwrap_or_default();
+            if access_key.is_empty() || secret_key.is_empty() {
+                return Err("AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY not found in environment variables".into());
+            }
+            let region = env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+            let session_token = env::var("AWS_SESSION_TOKEN").ok();
+            Ok(Box::new(AwsTranscribeBackend::new(region, access_key, secret_key, session_token)))
+        }
+    }
+}
+
+type AudioConsumer = Consumer<f32, Arc<SharedRb<f32, Vec<std::mem::MaybeUninit<f32>>>>>;
+
+/// Why the connected inner loop returned.
+enum LoopOutcome {
+    /// An explicit stop command was handled; the transcription task should exit.
+    Stopped,
+    /// The connection dropped (send/receive error or unexpected close); reconnect.
+    Disconnected,
 }
 
 pub async fn run_transcription_loop(
-    mut consumer: Consumer<f32, Arc<SharedRb<f32, Vec<std::mem::MaybeUninit<f32>>>>>,
+    mut consumer: AudioConsumer,
     input_sample_rate: u32,
     input_channels: u16,
     app_handle: AppHandle,
     mut cmd_rx: Receiver<TranscriptionCommand>,
+    config: TranscriptionConfig,
 ) {
     info!("Starting transcription loop. Input: {}Hz, {} channels", input_sample_rate, input_channels);
-    
+
     // Load .env if present
     dotenvy::dotenv().ok();
-    
-    let api_key = env::var("DEEPGRAM_API_KEY").unwrap_or_default();
-    if api_key.is_empty() {
-        error!("DEEPGRAM_API_KEY not found in environment variables");
-        app_handle.emit("status", "error: missing api key").ok();
-        return;
-    }
-
-    let mut meeting_note = MeetingNote::new();
 
-    // Connect to Deepgram
-    // encoding=linear16 means raw PCM 16-bit signed little-endian
-    let url_str = format!(
-        "wss://api.deepgram.com/v1/listen?model=nova-2&encoding=linear16&sample_rate={}&channels={}&smart_format=true&interim_results=true",
-        input_sample_rate, input_channels
-    );
-    let url = Url::parse(&url_str).expect("Invalid Deepgram URL");
+    let mut backend = match build_backend(&config.backend) {
+        Ok(b) => b,
+        Err(e) => {
+            error!("{}", e);
+            app_handle.emit("status", "error: missing api key").ok();
+            return;
+        }
+    };
 
-    let req = tokio_tungstenite::tungstenite::handshake::client::Request::builder()
-        .uri(url.as_str())
-        .header("Authorization", format!("Token {}", api_key))
-        .body(())
-        .unwrap();
+    let mut meeting_note = MeetingNote::new();
 
-    info!("Connecting to Deepgram...");
-    let (ws_stream, _) = match connect_async(req).await {
-        Ok(s) => s,
+    // Downmix to mono happens before streaming, so the STT backend always
+    // sees a single channel at the resampled rate.
+    let target_sample_rate = config.target_sample_rate;
+    let mut resampler = match Samplerate::new(
+        ConverterType::SincMediumQuality,
+        input_sample_rate,
+        target_sample_rate,
+        1,
+    ) {
+        Ok(r) => r,
         Err(e) => {
-            error!("Failed to connect to Deepgram: {}", e);
-            app_handle.emit("status", "error: connection failed").ok();
+            error!("Failed to create resampler: {}", e);
+            app_handle.emit("status", "error: resampler init failed").ok();
             return;
         }
     };
-    info!("Connected to Deepgram.");
-    
-    let (mut ws_write, mut ws_read) = ws_stream.split();
-    
+
+    let mut vad = VoiceActivityGate::new(
+        target_sample_rate,
+        frame_size_for_rate(target_sample_rate),
+        config.vad_hangover_frames,
+    );
+
+    let mut backoff = RECONNECT_BASE_BACKOFF;
+    let mut ever_connected = false;
+
+    'connect: loop {
+        info!("Connecting to STT backend...");
+        let outcome = match backend.connect(target_sample_rate, 1).await {
+            Ok(()) => {
+                info!("Connected to STT backend.");
+                ever_connected = true;
+                backoff = RECONNECT_BASE_BACKOFF;
+                app_handle.emit("status", "recording").ok();
+
+                run_connected_loop(
+                    backend.as_mut(),
+                    &mut consumer,
+                    input_channels,
+                    &mut resampler,
+                    &mut vad,
+                    &mut cmd_rx,
+                    &app_handle,
+                    &mut meeting_note,
+                )
+                .await
+            }
+            Err(e) if !ever_connected => {
+                error!("Failed to connect to STT backend: {}", e);
+                app_handle.emit("status", "error: connection failed").ok();
+                return;
+            }
+            // A failed reconnect attempt is retried with a longer backoff.
+            Err(e) => {
+                error!("Failed to reconnect to STT backend: {}", e);
+                LoopOutcome::Disconnected
+            }
+        };
+
+        match outcome {
+            LoopOutcome::Stopped => break,
+            LoopOutcome::Disconnected => {
+                let _ = backend.close().await;
+                app_handle.emit("status", "reconnecting").ok();
+
+                // Deliberately do NOT drain `consumer` while disconnected —
+                // captured audio just accumulates in the ring buffer (it has
+                // ~5 seconds of headroom) and gets flushed once we reconnect.
+                let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..100));
+
+                // Race the backoff sleep against Stop so a user request to
+                // stop is honored immediately instead of sitting in the
+                // channel for up to RECONNECT_MAX_BACKOFF while we're down.
+                tokio::select! {
+                    _ = sleep(backoff + jitter) => {
+                        backoff = (backoff * 2).min(RECONNECT_MAX_BACKOFF);
+                    }
+                    cmd = cmd_rx.recv() => {
+                        match cmd {
+                            Some(TranscriptionCommand::Stop) | None => {
+                                info!("Stop command received while reconnecting.");
+                                finish_and_notify_stopped(&meeting_note, &app_handle);
+                                break 'connect;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    info!("Exiting transcription loop");
+}
+
+/// Saves the meeting note and, only once that's actually done, tells the
+/// UI recording has stopped — so the app isn't closed mid-save and lose
+/// the transcript.
+fn finish_and_notify_stopped(meeting_note: &MeetingNote, app_handle: &AppHandle) {
+    match meeting_note.save_to_file(None) {
+        Ok(path) => info!("Meeting notes saved to: {:?}", path),
+        Err(e) => error!("Failed to save meeting notes: {}", e),
+    }
+    app_handle.emit("status", "stopped").ok();
+}
+
+/// Runs the audio-pump / response-handling loop against a connected
+/// backend until an explicit stop, or until the connection drops and
+/// needs to be reconnected.
+async fn run_connected_loop(
+    backend: &mut dyn TranscriptionBackend,
+    consumer: &mut AudioConsumer,
+    input_channels: u16,
+    resampler: &mut Samplerate,
+    vad: &mut VoiceActivityGate,
+    cmd_rx: &mut Receiver<TranscriptionCommand>,
+    app_handle: &AppHandle,
+    meeting_note: &mut MeetingNote,
+) -> LoopOutcome {
     // Timer to keep pumping audio data
     // 100ms chunks is a good balance for low latency
-    let mut ticker = interval(Duration::from_millis(50)); 
+    let mut ticker = interval(Duration::from_millis(50));
     let mut audio_buffer: Vec<i16> = Vec::with_capacity(4096);
-    
-    let mut active = true;
 
-    while active {
+    loop {
         tokio::select! {
              // Handle Cancellation
             cmd = cmd_rx.recv() => {
                 match cmd {
                     Some(TranscriptionCommand::Stop) | None => {
                         info!("Stop command received. Closing connection...");
-                        // Send empty frame or Close frame to finish?
-                        // Deepgram usually just closes.
-                        let _ = ws_write.send(Message::Close(None)).await;
-                        
-                        match meeting_note.save_to_file(None) {
-                             Ok(path) => info!("Meeting notes saved to: {:?}", path),
-                             Err(e) => error!("Failed to save meeting notes: {}", e),
-                        }
-                        active = false;
+                        let _ = backend.close().await;
+                        finish_and_notify_stopped(meeting_note, app_handle);
+                        return LoopOutcome::Stopped;
                     }
                 }
             }
-            
+
             // Handle Audio Input
             _ = ticker.tick() => {
                 let available = consumer.len();
                 if available > 0 {
-                     // We grab chunks
-                     // Note: iter() on consumer is not straightforward for slices.
-                     // We used unsafe advance which is efficient but we need to READ first.
-                     // IMPORTANT: ringbuf `pop_iter` or `pop_slice`.
-                     // Since we need to convert to i16, we iterate.
-                     
-                     // To avoid locking too long, limit chunk size?
-                     // 48000Hz * 0.05s = 2400 samples.
-                     // let chunk_size = std::cmp::min(available, 4800);
-                     
-                     // Ideally we pop into a temp buffer.
-                     // let mut f32_chunk = vec![0.0; chunk_size];
-                     // consumer.pop_slice(&mut f32_chunk);
-                     
-                     // Better: use iterator directly if possible, or simple loop
-                     // Simple loop popping one by one is slow.
-                     // Consumer implements iter() that yields items? No.
-                     // Use `pop_iter`
-                     
-                     // Optimization: Use slices if possible.
-                     // consumer.as_slices() returns (&[T], &[T]).
-                     
                      let (head, tail) = consumer.as_slices();
-                     let head_len = head.len();
-                     let tail_len = tail.len();
-                     
-                     // Process head
-                     for &sample in head {
-                         let s = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-                         audio_buffer.push(s);
+
+                     // Downmix interleaved input_channels to mono by averaging
+                     // the channels in each frame.
+                     let mut mono_chunk: Vec<f32> = Vec::with_capacity((head.len() + tail.len()) / input_channels as usize + 1);
+                     for frame in head.chunks_exact(input_channels as usize) {
+                         mono_chunk.push(frame.iter().sum::<f32>() / input_channels as f32);
                      }
-                     // Process tail
-                     for &sample in tail {
-                        let s = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-                        audio_buffer.push(s);
+                     for frame in tail.chunks_exact(input_channels as usize) {
+                         mono_chunk.push(frame.iter().sum::<f32>() / input_channels as f32);
+                     }
+
+                     // Advance consumer by whole frames only, leaving any partial
+                     // trailing frame for the next tick.
+                     let consumed_frames = mono_chunk.len();
+                     unsafe { consumer.advance(consumed_frames * input_channels as usize); }
+
+                     // Resample mono audio from the device's native rate to the
+                     // configured target rate with a stateful sinc converter so
+                     // frame boundaries don't click between ticks.
+                     if !mono_chunk.is_empty() {
+                         match resampler.process(&mono_chunk) {
+                             Ok(resampled) => {
+                                 // Gate out silence so we don't burn STT
+                                 // streaming minutes on long quiet stretches.
+                                 let (gated, transitions) = vad.process(&resampled);
+                                 for transition in transitions {
+                                     let status = match transition {
+                                         VadTransition::SpeechStarted => "speech",
+                                         VadTransition::SpeechEnded => "silence",
+                                     };
+                                     app_handle.emit("status", status).ok();
+                                 }
+                                 for &sample in &gated {
+                                     let s = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
+                                     audio_buffer.push(s);
+                                 }
+                             }
+                             Err(e) => error!("Resample error: {}", e),
+                         }
                      }
-                     
-                     // Advance consumer
-                     unsafe { consumer.advance(head_len + tail_len); }
-                     
+
                      // Send data if buffer is big enough
                      if !audio_buffer.is_empty() {
-                         // Convert Vec<i16> to Vec<u8> (bytes)
-                         let mut byte_data = Vec::with_capacity(audio_buffer.len() * 2);
-                         for sample in &audio_buffer {
-                             byte_data.extend_from_slice(&sample.to_le_bytes());
-                         }
-                         
-                         match ws_write.send(Message::Binary(byte_data)).await {
-                             Ok(_) => {},
-                             Err(e) => {
-                                 error!("WS Send Error: {}", e);
-                                 // break; // Optionally break or retry
-                             }
+                         if let Err(e) = backend.send_audio(&audio_buffer).await {
+                             error!("STT Send Error: {}", e);
+                             return LoopOutcome::Disconnected;
                          }
                          audio_buffer.clear();
                      }
                 }
             }
-            
-            // Handle Deepgram Responses
-            msg = ws_read.next() => {
-                match msg {
-                    Some(Ok(Message::Text(text))) => {
-                         // Parse JSON
-                         if let Ok(response) = serde_json::from_str::<DeepgramResponse>(&text) {
-                            if let Some(channel) = response.channel {
-                                if let Some(alt) = channel.alternatives.first() {
-                                    let transcript = &alt.transcript;
-                                    let is_final = response.is_final.unwrap_or(false);
-                                    
-                                    if !transcript.trim().is_empty() {
-                                         let timestamp = chrono::Utc::now().to_rfc3339();
-                                         let payload = serde_json::json!({
-                                             "text": transcript,
-                                             "is_final": is_final,
-                                             "timestamp": timestamp
-                                         });
-                                         
-                                         if is_final {
-                                             app_handle.emit("transcript_final", &payload).ok();
-                                             let display_time = chrono::Local::now().format("%H:%M:%S").to_string();
-                                             meeting_note.add_transcript_segment(transcript.clone(), display_time);
-                                         } else {
-                                             app_handle.emit("transcript_partial", &payload).ok();
-                                         }
-                                    }
-                                }
-                            }
-                         }
-                    }
-                    Some(Ok(Message::Close(_))) => {
-                        info!("Deepgram connection closed.");
-                        break;
-                    }
-                    Some(Err(e)) => {
-                        error!("WS Receive Error: {}", e);
-                        // break;
+
+            // Handle STT backend responses
+            result = backend.next_result() => {
+                match result {
+                    Some(transcript) if !transcript.text.trim().is_empty() => {
+                        let timestamp = chrono::Utc::now().to_rfc3339();
+                        let payload = serde_json::json!({
+                            "text": transcript.text,
+                            "is_final": transcript.is_final,
+                            "timestamp": timestamp
+                        });
+
+                        if transcript.is_final {
+                            app_handle.emit("transcript_final", &payload).ok();
+                            let display_time = chrono::Local::now().format("%H:%M:%S").to_string();
+                            meeting_note.add_transcript_segment(transcript.text, display_time);
+                        } else {
+                            app_handle.emit("transcript_partial", &payload).ok();
+                        }
                     }
+                    Some(_) => {}
                     None => {
-                        break;
+                        info!("STT backend connection closed.");
+                        return LoopOutcome::Disconnected;
                     }
-                    _ => {}
                 }
             }
         }
     }
-    
-    info!("Exiting transcription loop");
 }
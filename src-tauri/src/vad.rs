@@ -0,0 +1,139 @@
+use realfft::{RealFftPlanner, RealToComplex};
+use std::sync::Arc;
+
+const SPEECH_BAND_LOW_HZ: f32 = 300.0;
+const SPEECH_BAND_HIGH_HZ: f32 = 3400.0;
+// How quickly the noise floor is allowed to rise when the band stays loud;
+// it snaps down immediately instead, so transient noise never gets baked in.
+// Kept slow (time constant ~2s at 20ms frames) since the floor is already
+// frozen during speech — this only governs how fast a *louder room* gets
+// relearned as the new baseline.
+const NOISE_FLOOR_RISE_RATE: f32 = 0.01;
+const SPEECH_THRESHOLD_K: f32 = 3.0;
+const FRAME_DURATION_MS: u32 = 20;
+
+/// Samples per ~20ms analysis frame at `sample_rate`, so VAD timing (and
+/// the noise-floor/threshold tuning built around it) stays consistent
+/// however the caller has configured the target sample rate.
+pub fn frame_size_for_rate(sample_rate: u32) -> usize {
+    ((sample_rate * FRAME_DURATION_MS) / 1000) as usize
+}
+
+/// A speech/silence transition emitted by [`VoiceActivityGate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadTransition {
+    SpeechStarted,
+    SpeechEnded,
+}
+
+/// Spectral voice-activity gate: buffers mono samples into fixed-size
+/// frames, classifies each as speech or silence from its energy in the
+/// speech band, and only lets speech (plus a trailing hangover) through.
+pub struct VoiceActivityGate {
+    frame_size: usize,
+    window: Vec<f32>,
+    fft: Arc<dyn RealToComplex<f32>>,
+    band_lo_bin: usize,
+    band_hi_bin: usize,
+    noise_floor: f32,
+    hangover_frames: usize,
+    hangover_remaining: usize,
+    speech_active: bool,
+    pending: Vec<f32>,
+}
+
+impl VoiceActivityGate {
+    /// `frame_size` samples per analysis frame (~20ms worth of samples at
+    /// whatever rate the caller is feeding in — see [`frame_size_for_rate`]).
+    /// `hangover_frames` trailing silent frames still forwarded after
+    /// speech ends, so word endings aren't clipped.
+    pub fn new(sample_rate: u32, frame_size: usize, hangover_frames: usize) -> Self {
+        let window: Vec<f32> = (0..frame_size)
+            .map(|i| 0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / (frame_size - 1) as f32).cos()))
+            .collect();
+
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(frame_size);
+
+        let bin_hz = sample_rate as f32 / frame_size as f32;
+        let band_lo_bin = (SPEECH_BAND_LOW_HZ / bin_hz).floor() as usize;
+        let band_hi_bin = (SPEECH_BAND_HIGH_HZ / bin_hz).ceil() as usize;
+
+        Self {
+            frame_size,
+            window,
+            fft,
+            band_lo_bin,
+            band_hi_bin,
+            noise_floor: f32::MAX,
+            hangover_frames,
+            hangover_remaining: 0,
+            speech_active: false,
+            pending: Vec::with_capacity(frame_size * 2),
+        }
+    }
+
+    /// Feeds newly resampled mono samples. Returns the subset that should
+    /// be forwarded to the STT backend (speech frames plus the trailing
+    /// hangover window) and any speech/silence transitions that occurred.
+    pub fn process(&mut self, samples: &[f32]) -> (Vec<f32>, Vec<VadTransition>) {
+        self.pending.extend_from_slice(samples);
+
+        let mut forwarded = Vec::new();
+        let mut transitions = Vec::new();
+
+        while self.pending.len() >= self.frame_size {
+            let frame: Vec<f32> = self.pending.drain(..self.frame_size).collect();
+            let is_speech = self.classify(&frame);
+
+            if is_speech {
+                if !self.speech_active {
+                    self.speech_active = true;
+                    transitions.push(VadTransition::SpeechStarted);
+                }
+                self.hangover_remaining = self.hangover_frames;
+                forwarded.extend_from_slice(&frame);
+            } else if self.hangover_remaining > 0 {
+                self.hangover_remaining -= 1;
+                forwarded.extend_from_slice(&frame);
+            } else if self.speech_active {
+                self.speech_active = false;
+                transitions.push(VadTransition::SpeechEnded);
+            }
+        }
+
+        (forwarded, transitions)
+    }
+
+    fn classify(&mut self, frame: &[f32]) -> bool {
+        let mut input = self.fft.make_input_vec();
+        for (dst, (&sample, &w)) in input.iter_mut().zip(frame.iter().zip(self.window.iter())) {
+            *dst = sample * w;
+        }
+
+        let mut spectrum = self.fft.make_output_vec();
+        let mut scratch = self.fft.make_scratch_vec();
+        if self.fft.process_with_scratch(&mut input, &mut spectrum, &mut scratch).is_err() {
+            return false;
+        }
+
+        let hi_bin = self.band_hi_bin.min(spectrum.len() - 1);
+        let band_energy: f32 = spectrum[self.band_lo_bin..=hi_bin].iter().map(|c| c.norm_sqr()).sum();
+
+        // Only adapt the floor from frames classified as silence on the
+        // *previous* frame. Otherwise a sustained speech turn pulls the
+        // floor up to ~energy/K within a few hundred ms and the gate stops
+        // forwarding audio mid-utterance.
+        if !self.speech_active {
+            if band_energy < self.noise_floor {
+                // Snap down immediately when the floor drops.
+                self.noise_floor = band_energy;
+            } else {
+                // Slowly adapt upward so transient noise doesn't get baked in.
+                self.noise_floor += (band_energy - self.noise_floor) * NOISE_FLOOR_RISE_RATE;
+            }
+        }
+
+        band_energy > self.noise_floor.max(f32::MIN_POSITIVE) * SPEECH_THRESHOLD_K
+    }
+}